@@ -1,53 +1,127 @@
-use std::{collections::HashMap, fmt::Display, fs::read_to_string, str::FromStr};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+    fmt::Display,
+    fs::{read_to_string, write},
+    str::FromStr,
+};
 
 use clap::Parser;
 use nom::{
     bytes::complete::tag,
-    character::complete::{alpha1, space1},
+    character::complete::{alpha1, digit1, space1},
+    combinator::{cut, map_res, opt},
     multi::{separated_list0, separated_list1},
+    sequence::preceded,
     Finish, IResult,
 };
-use pathfinding::directed::dijkstra;
-
-/// The color of the edge between two nodes. A more generalized solution may have more colors or
-/// may require altering the algorithm. We'll show a solution though for just two colors.
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
-enum Color {
-    Red,
-    Blue,
-    None,
+use pathfinding::directed::{astar::astar_bag, dijkstra};
+
+/// The color of an edge between two nodes. Colors are arbitrary tokens (e.g. "red", "blue",
+/// "green"), so this wraps the parsed string rather than enumerating a fixed set.
+///
+/// `Color::start()` is a sentinel used only for the synthetic edge a path begins on, before any
+/// real edge has been traversed. It can never collide with a parsed color because colors come
+/// from `alpha1` and are therefore never empty.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Color(String);
+
+impl Color {
+    /// The sentinel color for the start of a path.
+    fn start() -> Self {
+        Color(String::new())
+    }
+
+    /// Whether this is the start-of-path sentinel.
+    fn is_start(&self) -> bool {
+        self.0.is_empty()
+    }
 }
 
 impl Display for Color {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Color::Red => write!(f, "red"),
-            Color::Blue => write!(f, "blue"),
-            Color::None => write!(f, "none"),
+        if self.is_start() {
+            write!(f, "start")
+        } else {
+            write!(f, "{}", self.0)
         }
     }
 }
 
 impl From<&str> for Color {
     fn from(s: &str) -> Self {
-        match s {
-            "red" => Color::Red,
-            "blue" => Color::Blue,
-            _ => Color::None,
-        }
+        Color(s.to_string())
     }
 }
 
-/// An edge in the graph.
+/// An edge in the graph. `cost` is the explicit weight parsed from the input (e.g. the `3` in
+/// `red:b:3`), if the input specified one; `None` means the edge falls back to a `--color-weight`
+/// override or, failing that, the default cost of `1`.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 struct Edge {
     color: Color,
     node: String,
+    cost: Option<usize>,
 }
 
 impl Edge {
     fn new(color: Color, node: String) -> Self {
-        Edge { color, node }
+        Edge {
+            color,
+            node,
+            cost: None,
+        }
+    }
+
+    /// The edge's effective cost: its own explicit cost if the input specified one, otherwise the
+    /// `--color-weight` override for its color, otherwise `1`.
+    fn cost(&self, color_weights: &HashMap<String, usize>) -> usize {
+        self.cost
+            .unwrap_or_else(|| color_weights.get(&self.color.0).copied().unwrap_or(1))
+    }
+}
+
+/// A position while solving the puzzle: the current node, the color of the edge last traversed
+/// to reach it, and how many consecutive edges of that color have been used in a row. `run` is
+/// meaningless while `color` is the start sentinel.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct State {
+    node: String,
+    color: Color,
+    run: usize,
+}
+
+/// One traversal step, identified by the node it leaves from, the color of the edge taken, and
+/// the node it arrives at (ignoring the edge's cost, which doesn't affect which edge was used).
+/// Used by [`Puzzle::solve_k`] to block off edges already used by an accepted path while
+/// searching for the next-best one.
+type Step = (String, Color, String);
+
+/// A candidate path found while running Yen's algorithm in [`Puzzle::solve_k`], ordered by its
+/// total cost so the cheapest unused candidate can always be popped off the heap next.
+#[derive(Clone, Debug)]
+struct Candidate {
+    cost: usize,
+    states: Vec<State>,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.cost.cmp(&other.cost)
     }
 }
 
@@ -74,6 +148,10 @@ impl FromStr for Puzzle {
 }
 
 impl Puzzle {
+    /// Sentinel node used as the synthetic root of a multi-source search in [`Puzzle::solve`].
+    /// Real node names come from `alpha1`, so this can never collide with one.
+    const ROOT: &'static str = "\0";
+
     /// Parse a puzzle from a string.
     fn parse_puzzle(input: &str) -> IResult<&str, Puzzle> {
         let (input, nodes) = separated_list0(tag("\n"), Puzzle::parse_node)(input)?;
@@ -93,62 +171,431 @@ impl Puzzle {
         Ok((input, (node.to_string(), edges)))
     }
 
-    /// Parse an edge from a string (e.g. "red:b"). Used in parsing.
+    /// Parse an edge from a string (e.g. "red:b" or, with an explicit cost, "red:b:3"). Used in
+    /// parsing.
     fn parse_edge(input: &str) -> IResult<&str, Edge> {
         let (input, color) = alpha1(input)?;
         let (input, _) = tag(":")(input)?;
         let (input, node) = alpha1(input)?;
-        Ok((input, Edge::new(Color::from(color), node.to_string())))
+        // `cut` turns an out-of-range cost (e.g. one that overflows `usize`) into a hard parse
+        // failure rather than a recoverable one, so `opt` doesn't silently treat it as "no cost
+        // given" and swallow the bad input.
+        let (input, cost) = opt(preceded(
+            tag(":"),
+            cut(map_res(digit1, |c: &str| c.parse::<usize>())),
+        ))(input)?;
+        Ok((
+            input,
+            Edge {
+                color: Color::from(color),
+                node: node.to_string(),
+                cost,
+            },
+        ))
     }
 
-    /// Solve the puzzle by finding the shortest path from the start node to the end node.
-    fn solve(&self, start: String, end: String) -> Option<Vec<Edge>> {
-        // We start at the start node with no color.
-        let start = Edge::new(Color::None, start);
-
-        // We've reached the end node when the current node is the end node.
-        let success = |state: &Edge| state.node == end;
-
-        // The successors of a state are the nodes that are connected to the current node. We filter
-        // out nodes that are the same color as the current state. Dijkstra's algorithm uses a
-        // weighted graph, but we don't need to weight the edges in this case so we always return 1.
-        let successors = |edge: &Edge| {
-            let edges = self.nodes.get(&edge.node).unwrap();
-            edges
+    /// The successors of a state are the nodes connected to the current node. Any color is allowed
+    /// from the start sentinel. Otherwise, continuing with the same color is only allowed while
+    /// we're under max_run, and switching to a different color is only allowed once we've reached
+    /// min_run. Each successor is weighted by [`Edge::cost`], so unweighted edges default to `1`.
+    ///
+    /// `excluding`, when given, is a `(removed_edges, removed_nodes)` pair that the caller wants
+    /// treated as not existing / unreachable. [`Puzzle::solve_k`] uses this to search for
+    /// alternatives to already-accepted paths without recreating them.
+    fn successors<'a>(
+        &'a self,
+        min_run: usize,
+        max_run: usize,
+        color_weights: &'a HashMap<String, usize>,
+        excluding: Option<(&'a HashSet<Step>, &'a HashSet<String>)>,
+    ) -> impl Fn(&State) -> Vec<(State, usize)> + 'a {
+        move |state: &State| {
+            if excluding.is_some_and(|(_, removed_nodes)| removed_nodes.contains(&state.node)) {
+                return Vec::new();
+            }
+            // A node with no outgoing edges (e.g. a dead end, or the goal itself) has no entry in
+            // `self.nodes` rather than an empty one, so treat a missing node as having no edges.
+            self.nodes
+                .get(&state.node)
+                .map(Vec::as_slice)
+                .unwrap_or(&[])
                 .iter()
-                .filter_map(|Edge { color, node }| {
-                    if *color == Color::None || edge.color != *color {
-                        Some(Edge {
-                            color: *color,
-                            node: node.clone(),
+                .filter(|edge| {
+                    !matches!(excluding, Some((removed_edges, removed_nodes))
+                    if removed_nodes.contains(&edge.node) || removed_edges.contains(&(
+                        state.node.clone(),
+                        edge.color.clone(),
+                        edge.node.clone(),
+                    )))
+                })
+                .filter_map(|edge| {
+                    let next = if state.color.is_start() {
+                        Some(State {
+                            node: edge.node.clone(),
+                            color: edge.color.clone(),
+                            run: 1,
+                        })
+                    } else if edge.color == state.color {
+                        (state.run < max_run).then(|| State {
+                            node: edge.node.clone(),
+                            color: edge.color.clone(),
+                            run: state.run + 1,
                         })
                     } else {
-                        None
-                    }
+                        (state.run >= min_run).then(|| State {
+                            node: edge.node.clone(),
+                            color: edge.color.clone(),
+                            run: 1,
+                        })
+                    };
+                    next.map(|next| (next, edge.cost(color_weights)))
+                })
+                .collect::<Vec<(State, usize)>>()
+        }
+    }
+
+    /// Solve the puzzle by finding the minimum-cost path from any node in `starts` to the nearest
+    /// reachable node in `ends`, using at most `max_run` consecutive edges of the same color and
+    /// requiring at least `min_run` before switching to a different color. `color_weights`
+    /// overrides the cost of edges that don't specify an explicit cost in the input; unweighted,
+    /// unoverridden edges cost `1`.
+    ///
+    /// This is a multi-source search: we run a single Dijkstra from a synthetic root state that,
+    /// in one free step, fans out to a zero-cost edge into each start node. The returned path
+    /// (and so `solution.first()`/`solution.last()`) reveals which start/end pair was matched —
+    /// the synthetic root itself is dropped before returning.
+    fn solve(
+        &self,
+        starts: Vec<String>,
+        ends: Vec<String>,
+        min_run: usize,
+        max_run: usize,
+        color_weights: &HashMap<String, usize>,
+    ) -> Option<Vec<Edge>> {
+        let root = State {
+            node: Self::ROOT.to_string(),
+            color: Color::start(),
+            run: 0,
+        };
+
+        let ends: HashSet<String> = ends.into_iter().collect();
+        let success = |state: &State| ends.contains(&state.node) && state.run >= min_run;
+
+        let base_successors = self.successors(min_run, max_run, color_weights, None);
+        let successors = move |state: &State| {
+            if state.node == Self::ROOT {
+                starts
+                    .iter()
+                    .map(|start| {
+                        (
+                            State {
+                                node: start.clone(),
+                                color: Color::start(),
+                                run: 0,
+                            },
+                            0,
+                        )
+                    })
+                    .collect()
+            } else {
+                base_successors(state)
+            }
+        };
+
+        // Run Dijkstra's algorithm to find the shortest path, then convert the states back into
+        // the edges that were traversed, dropping the synthetic root.
+        dijkstra::dijkstra(&root, successors, success).map(|(states, _)| {
+            states
+                .into_iter()
+                .skip(1)
+                .map(|state| Edge::new(state.color, state.node))
+                .collect()
+        })
+    }
+
+    /// The total weighted cost of traversing `states` in order, i.e. the sum of [`Edge::cost`] for
+    /// the edge taken between each consecutive pair of states. Used by [`Puzzle::solve_k`] to cost
+    /// a root path, since a `State` only records the color and node it arrived on, not what that
+    /// step cost.
+    fn path_cost(&self, states: &[State], color_weights: &HashMap<String, usize>) -> usize {
+        states
+            .windows(2)
+            .map(|window| {
+                let edge = self.nodes[&window[0].node]
+                    .iter()
+                    .find(|edge| edge.color == window[1].color && edge.node == window[1].node)
+                    .unwrap();
+                edge.cost(color_weights)
+            })
+            .sum()
+    }
+
+    /// Solve the puzzle like [`Puzzle::solve`], but return every shortest path tied for the minimum
+    /// length instead of just one.
+    fn solve_all(
+        &self,
+        start: String,
+        end: String,
+        min_run: usize,
+        max_run: usize,
+        color_weights: &HashMap<String, usize>,
+    ) -> Vec<Vec<Edge>> {
+        let start = State {
+            node: start,
+            color: Color::start(),
+            run: 0,
+        };
+        let success = |state: &State| state.node == end && state.run >= min_run;
+
+        // astar_bag behaves like Dijkstra with a zero heuristic, but bags up every path that
+        // reaches the goal at the optimal cost instead of returning just one.
+        match astar_bag(
+            &start,
+            self.successors(min_run, max_run, color_weights, None),
+            |_| 0,
+            success,
+        ) {
+            Some((paths, _)) => paths
+                .map(|states| {
+                    states
+                        .into_iter()
+                        .map(|state| Edge::new(state.color, state.node))
+                        .collect()
                 })
-                .map(|edge| (edge, 1))
-                .collect::<Vec<(Edge, usize)>>()
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Find the `k` best distinct valid paths from `start` to `end`, using Yen's algorithm over the
+    /// color-constrained successor function. The shortest path is found first; each subsequent path
+    /// is the best "spur" found by, for every prefix of the previous path, blocking off the edges
+    /// and root-path nodes that would recreate an already-accepted path and re-solving from the spur
+    /// node to `end`. Candidates are kept in a min-heap keyed by total length so the next path is
+    /// always the cheapest one not yet accepted. Returns the paths in increasing order of length,
+    /// paired with their length.
+    fn solve_k(
+        &self,
+        start: String,
+        end: String,
+        min_run: usize,
+        max_run: usize,
+        k: usize,
+        color_weights: &HashMap<String, usize>,
+    ) -> Vec<(Vec<Edge>, usize)> {
+        if k == 0 {
+            return Vec::new();
+        }
+
+        let start_state = State {
+            node: start,
+            color: Color::start(),
+            run: 0,
         };
+        let success = |state: &State| state.node == end && state.run >= min_run;
+
+        let no_edges = HashSet::new();
+        let no_nodes = HashSet::new();
+        let Some((first_path, first_cost)) = dijkstra::dijkstra(
+            &start_state,
+            self.successors(
+                min_run,
+                max_run,
+                color_weights,
+                Some((&no_edges, &no_nodes)),
+            ),
+            success,
+        ) else {
+            return Vec::new();
+        };
+
+        let mut seen = HashSet::new();
+        seen.insert(first_path.clone());
+        let mut found = vec![(first_path, first_cost)];
+        let mut candidates: BinaryHeap<Reverse<Candidate>> = BinaryHeap::new();
 
-        // Run Dijkstra's algorithm to find the shortest path.
-        dijkstra::dijkstra(&start, successors, success).map(|(solution, _)| solution)
+        while found.len() < k {
+            let prev_path = found.last().unwrap().0.clone();
+
+            for i in 0..prev_path.len().saturating_sub(1) {
+                let spur_state = &prev_path[i];
+                let root_path = &prev_path[..=i];
+
+                let mut removed_edges = HashSet::new();
+                for (path, _) in &found {
+                    if path.len() > i + 1 && path[..=i] == *root_path {
+                        removed_edges.insert((
+                            path[i].node.clone(),
+                            path[i + 1].color.clone(),
+                            path[i + 1].node.clone(),
+                        ));
+                    }
+                }
+                let removed_nodes: HashSet<String> = root_path[..i]
+                    .iter()
+                    .map(|state| state.node.clone())
+                    .collect();
+                let root_cost = self.path_cost(root_path, color_weights);
+
+                if let Some((spur_path, spur_cost)) = dijkstra::dijkstra(
+                    spur_state,
+                    self.successors(
+                        min_run,
+                        max_run,
+                        color_weights,
+                        Some((&removed_edges, &removed_nodes)),
+                    ),
+                    success,
+                ) {
+                    let mut total_path = root_path[..i].to_vec();
+                    total_path.extend(spur_path);
+                    if seen.insert(total_path.clone()) {
+                        candidates.push(Reverse(Candidate {
+                            cost: root_cost + spur_cost,
+                            states: total_path,
+                        }));
+                    }
+                }
+            }
+
+            let Some(Reverse(candidate)) = candidates.pop() else {
+                break;
+            };
+            found.push((candidate.states, candidate.cost));
+        }
+
+        found
+            .into_iter()
+            .map(|(states, cost)| {
+                (
+                    states
+                        .into_iter()
+                        .map(|state| Edge::new(state.color, state.node))
+                        .collect(),
+                    cost,
+                )
+            })
+            .collect()
+    }
+
+    /// Render the graph as Graphviz DOT, with every edge colored by its [`Color`]. When
+    /// `solution` is given (as returned by [`Puzzle::solve`]), the edges it traverses are drawn
+    /// bold and labeled with their traversal order, so the route can be read off the picture. A
+    /// node can be entered more than once on the same path (with the same or a different incoming
+    /// color each time), so a traversed edge is keyed by the `(from, color, to)` step it
+    /// represents rather than just the nodes it connects, and its label lists every order number
+    /// it was used at.
+    fn to_dot(&self, solution: Option<&[Edge]>) -> String {
+        let mut traversal_order: HashMap<Step, Vec<usize>> = HashMap::new();
+        if let Some(solution) = solution {
+            for (order, window) in solution.windows(2).enumerate() {
+                let (from, to) = (&window[0], &window[1]);
+                let step = (from.node.clone(), to.color.clone(), to.node.clone());
+                traversal_order.entry(step).or_default().push(order + 1);
+            }
+        }
+
+        let mut nodes: Vec<&String> = self.nodes.keys().collect();
+        nodes.sort();
+
+        let mut dot = String::from("digraph wall {\n");
+        for node in nodes {
+            for edge in &self.nodes[node] {
+                let step = (node.clone(), edge.color.clone(), edge.node.clone());
+                match traversal_order.get(&step) {
+                    Some(orders) => {
+                        let orders = orders
+                            .iter()
+                            .map(usize::to_string)
+                            .collect::<Vec<_>>()
+                            .join(",");
+                        dot.push_str(&format!(
+                            "  \"{}\" -> \"{}\" [label=\"{} ({})\", color=\"{}\", penwidth=3];\n",
+                            node, edge.node, edge.color, orders, edge.color
+                        ));
+                    }
+                    None => {
+                        dot.push_str(&format!(
+                            "  \"{}\" -> \"{}\" [label=\"{}\", color=\"{}\"];\n",
+                            node, edge.node, edge.color, edge.color
+                        ));
+                    }
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
     }
 }
 
 /// Solve the wall puzzle. The wall puzzle is a graph where each node is a letter and each edge is
-/// a color. The goal is to find the shortest path from the start node to the end node where no two
-/// adjacent edges used are the same color.
+/// a color. The goal is to find the shortest path from the start node to the end node using at
+/// most `max-run` consecutive edges of the same color, switching only once `min-run` has been
+/// reached.
 #[derive(Parser)]
 #[command(version, about, long_about=None)]
 struct Cli {
     /// The input file containing the puzzle.
     file: String,
 
-    /// The start node.
+    /// The start node, or a comma-separated list of start nodes. The path may begin from any of
+    /// them.
     start: String,
 
-    /// The end node.
+    /// The end node, or a comma-separated list of end nodes. The path finishes at whichever one
+    /// is nearest.
     end: String,
+
+    /// The minimum number of consecutive edges of the same color required before switching color.
+    #[arg(long, default_value_t = 0)]
+    min_run: usize,
+
+    /// The maximum number of consecutive edges of the same color allowed.
+    #[arg(long, default_value_t = 1)]
+    max_run: usize,
+
+    /// Print every shortest solution instead of just one.
+    #[arg(long)]
+    all: bool,
+
+    /// Print only the number of distinct shortest solutions.
+    #[arg(long)]
+    count_only: bool,
+
+    /// Print the K best distinct paths, shortest first, instead of just the single best one.
+    #[arg(long)]
+    k: Option<usize>,
+
+    /// Per-color edge weight overrides, as a comma-separated list of `color=weight` pairs (e.g.
+    /// `red=2,blue=1`). Only applies to edges that don't specify an explicit cost in the input.
+    #[arg(long)]
+    color_weight: Option<String>,
+
+    /// Write the graph as Graphviz DOT to this file, highlighting the computed solution (if any).
+    #[arg(long)]
+    dot: Option<String>,
+}
+
+/// Parse the `start`/`end` arguments, which are either a single node or a comma-separated list of
+/// nodes.
+fn parse_nodes(s: &str) -> Vec<String> {
+    s.split(',').map(str::to_string).collect()
+}
+
+/// Parse a `--color-weight` argument like `red=2,blue=1` into a map from color name to weight.
+fn parse_color_weights(s: &str) -> Result<HashMap<String, usize>, String> {
+    s.split(',')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let (color, weight) = part
+                .split_once('=')
+                .ok_or_else(|| format!("invalid --color-weight entry: {part}"))?;
+            let weight = weight
+                .parse()
+                .map_err(|_| format!("invalid --color-weight weight: {part}"))?;
+            Ok((color.to_string(), weight))
+        })
+        .collect()
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -156,11 +603,90 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
     let input = read_to_string(cli.file)?;
     let puzzle: Puzzle = input.parse()?;
+    let color_weights = parse_color_weights(cli.color_weight.as_deref().unwrap_or(""))?;
+    let starts = parse_nodes(&cli.start);
+    let ends = parse_nodes(&cli.end);
 
-    // Solve the puzzle and print the solution.
-    match puzzle.solve(cli.start, cli.end) {
-        Some(solution) => print_solution(solution),
-        None => println!("No solution found"),
+    // `--k`, `--all` and `--count-only` search from a single start/end pair rather than the
+    // multi-source frontier [`Puzzle::solve`] uses, so a start/end list is only unambiguous when
+    // it names exactly one node; reject it outright (before running anything below, so a rejected
+    // combination never writes a `--dot` file) rather than silently solving for the first node and
+    // dropping the rest.
+    if (cli.k.is_some() || cli.count_only || cli.all) && (starts.len() > 1 || ends.len() > 1) {
+        return Err(
+            "--k, --all and --count-only don't support comma-separated start/end lists; \
+                     pass a single start node and a single end node"
+                .into(),
+        );
+    }
+
+    // If requested, export the graph (and the single best solution, if one exists) as Graphviz
+    // DOT before running whichever solve mode was asked for below.
+    if let Some(path) = &cli.dot {
+        let solution = puzzle.solve(
+            starts.clone(),
+            ends.clone(),
+            cli.min_run,
+            cli.max_run,
+            &color_weights,
+        );
+        write(path, puzzle.to_dot(solution.as_deref()))?;
+    }
+
+    let first_start = starts[0].clone();
+    let first_end = ends[0].clone();
+
+    // Solve the puzzle and print the solution(s).
+    if let Some(k) = cli.k {
+        let paths = puzzle.solve_k(
+            first_start,
+            first_end,
+            cli.min_run,
+            cli.max_run,
+            k,
+            &color_weights,
+        );
+        if paths.is_empty() {
+            println!("No solution found");
+        }
+        for (i, (path, cost)) in paths.into_iter().enumerate() {
+            if i > 0 {
+                println!();
+            }
+            println!("Path {} (length {}):", i + 1, cost);
+            print_solution(path);
+        }
+    } else if cli.count_only {
+        let solutions = puzzle.solve_all(
+            first_start,
+            first_end,
+            cli.min_run,
+            cli.max_run,
+            &color_weights,
+        );
+        println!("{}", solutions.len());
+    } else if cli.all {
+        let solutions = puzzle.solve_all(
+            first_start,
+            first_end,
+            cli.min_run,
+            cli.max_run,
+            &color_weights,
+        );
+        if solutions.is_empty() {
+            println!("No solution found");
+        }
+        for (i, solution) in solutions.into_iter().enumerate() {
+            if i > 0 {
+                println!();
+            }
+            print_solution(solution);
+        }
+    } else {
+        match puzzle.solve(starts, ends, cli.min_run, cli.max_run, &color_weights) {
+            Some(solution) => print_solution(solution),
+            None => println!("No solution found"),
+        }
     }
     Ok(())
 }
@@ -184,9 +710,10 @@ mod tests {
 
     #[test]
     fn test_parse_color() {
-        assert_eq!(Color::from("red"), Color::Red);
-        assert_eq!(Color::from("blue"), Color::Blue);
-        assert_eq!(Color::from("green"), Color::None);
+        assert_eq!(Color::from("red"), Color("red".to_string()));
+        assert_eq!(Color::from("blue"), Color("blue".to_string()));
+        assert_eq!(Color::from("green"), Color("green".to_string()));
+        assert_ne!(Color::from("green"), Color::start());
     }
 
     #[test]
@@ -198,15 +725,15 @@ mod tests {
         expected.insert(
             "a".to_string(),
             vec![
-                Edge::new(Color::Red, "b".to_string()),
-                Edge::new(Color::Blue, "a".to_string()),
+                Edge::new(Color::from("red"), "b".to_string()),
+                Edge::new(Color::from("blue"), "a".to_string()),
             ],
         );
         expected.insert(
             "c".to_string(),
             vec![
-                Edge::new(Color::Red, "a".to_string()),
-                Edge::new(Color::Blue, "b".to_string()),
+                Edge::new(Color::from("red"), "a".to_string()),
+                Edge::new(Color::from("blue"), "b".to_string()),
             ],
         );
 
@@ -217,17 +744,55 @@ mod tests {
     fn test_solve_simple() {
         let input = "a red:b \nb blue:a";
         let puzzle: Puzzle = input.parse().unwrap();
-        let solution = puzzle.solve("a".to_string(), "b".to_string());
+        let solution = puzzle.solve(
+            vec!["a".to_string()],
+            vec!["b".to_string()],
+            0,
+            1,
+            &HashMap::new(),
+        );
         assert_eq!(
             solution,
             Some(vec![
                 Edge {
-                    color: Color::None,
-                    node: "a".to_string()
+                    color: Color::start(),
+                    node: "a".to_string(),
+                    cost: None,
                 },
                 Edge {
-                    color: Color::Red,
-                    node: "b".to_string()
+                    color: Color::from("red"),
+                    node: "b".to_string(),
+                    cost: None,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_solve_multi_source() {
+        // "a" can't reach "b" directly, but "c" can in one hop, so the nearest start/end pair is
+        // (c, b) even though "a" sorts first.
+        let input = "a red:c\nc blue:b\nb red:a";
+        let puzzle: Puzzle = input.parse().unwrap();
+        let solution = puzzle.solve(
+            vec!["a".to_string(), "c".to_string()],
+            vec!["b".to_string()],
+            0,
+            1,
+            &HashMap::new(),
+        );
+        assert_eq!(
+            solution,
+            Some(vec![
+                Edge {
+                    color: Color::start(),
+                    node: "c".to_string(),
+                    cost: None,
+                },
+                Edge {
+                    color: Color::from("blue"),
+                    node: "b".to_string(),
+                    cost: None,
                 },
             ])
         );
@@ -237,47 +802,191 @@ mod tests {
     fn test_solve_complex() {
         let input = include_str!("../wall-puzzle.txt");
         let puzzle: Puzzle = input.parse().unwrap();
-        let solution = puzzle.solve("s".to_string(), "t".to_string());
+        let solution = puzzle.solve(
+            vec!["s".to_string()],
+            vec!["t".to_string()],
+            0,
+            1,
+            &HashMap::new(),
+        );
         assert_eq!(
             solution,
             Some(vec![
                 Edge {
-                    color: Color::None,
-                    node: "s".to_string()
+                    color: Color::start(),
+                    node: "s".to_string(),
+                    cost: None,
                 },
                 Edge {
-                    color: Color::Red,
-                    node: "a".to_string()
+                    color: Color::from("red"),
+                    node: "a".to_string(),
+                    cost: None,
                 },
                 Edge {
-                    color: Color::Blue,
-                    node: "b".to_string()
+                    color: Color::from("blue"),
+                    node: "b".to_string(),
+                    cost: None,
                 },
                 Edge {
-                    color: Color::Red,
-                    node: "c".to_string()
+                    color: Color::from("red"),
+                    node: "c".to_string(),
+                    cost: None,
                 },
                 Edge {
-                    color: Color::Blue,
-                    node: "c".to_string()
+                    color: Color::from("blue"),
+                    node: "c".to_string(),
+                    cost: None,
                 },
                 Edge {
-                    color: Color::Red,
-                    node: "b".to_string()
+                    color: Color::from("red"),
+                    node: "b".to_string(),
+                    cost: None,
                 },
                 Edge {
-                    color: Color::Blue,
-                    node: "a".to_string()
+                    color: Color::from("blue"),
+                    node: "a".to_string(),
+                    cost: None,
                 },
                 Edge {
-                    color: Color::Red,
-                    node: "e".to_string()
+                    color: Color::from("red"),
+                    node: "e".to_string(),
+                    cost: None,
                 },
                 Edge {
-                    color: Color::Blue,
-                    node: "t".to_string()
+                    color: Color::from("blue"),
+                    node: "t".to_string(),
+                    cost: None,
                 }
             ])
         );
     }
+
+    #[test]
+    fn test_solve_max_run_boundary() {
+        // Two consecutive red edges: allowed once max_run reaches 2, blocked at max_run 1 since
+        // there's no other way to reach "c".
+        let input = "a red:b\nb red:c";
+        let puzzle: Puzzle = input.parse().unwrap();
+
+        let blocked = puzzle.solve(
+            vec!["a".to_string()],
+            vec!["c".to_string()],
+            0,
+            1,
+            &HashMap::new(),
+        );
+        assert_eq!(blocked, None);
+
+        let allowed = puzzle.solve(
+            vec!["a".to_string()],
+            vec!["c".to_string()],
+            0,
+            2,
+            &HashMap::new(),
+        );
+        assert!(allowed.is_some());
+    }
+
+    #[test]
+    fn test_solve_min_run_boundary() {
+        // Switching from red to blue after a single red edge: allowed once min_run drops to 1,
+        // blocked at min_run 2 since the run hasn't reached the minimum yet.
+        let input = "a red:b\nb blue:c";
+        let puzzle: Puzzle = input.parse().unwrap();
+
+        let blocked = puzzle.solve(
+            vec!["a".to_string()],
+            vec!["c".to_string()],
+            2,
+            2,
+            &HashMap::new(),
+        );
+        assert_eq!(blocked, None);
+
+        let allowed = puzzle.solve(
+            vec!["a".to_string()],
+            vec!["c".to_string()],
+            1,
+            2,
+            &HashMap::new(),
+        );
+        assert!(allowed.is_some());
+    }
+
+    #[test]
+    fn test_solve_all_enumerates_tied_shortest_paths() {
+        // Two node-disjoint paths from "a" to "d", both two edges long, so both are tied for
+        // shortest and solve_all should return both rather than just the first one found. Each
+        // path switches color on its second edge so it stays valid under the default max_run of 1.
+        let input = "a red:b blue:c\nb blue:d\nc red:d";
+        let puzzle: Puzzle = input.parse().unwrap();
+
+        let solutions = puzzle.solve_all("a".to_string(), "d".to_string(), 0, 1, &HashMap::new());
+
+        let via: HashSet<String> = solutions
+            .iter()
+            .map(|solution| solution[1].node.clone())
+            .collect();
+        assert_eq!(via, HashSet::from(["b".to_string(), "c".to_string()]));
+        assert_eq!(solutions.len(), 2);
+    }
+
+    #[test]
+    fn test_solve_k_orders_by_cost_and_does_not_invent_duplicates() {
+        // Only two distinct simple paths exist from "s" to "t", with costs 2 and 3. Each path
+        // switches color on its second edge to stay valid under the default max_run of 1. Asking
+        // for more than that (k=5) should still return exactly those two, cheapest first, rather
+        // than duplicating one of them to pad out to k.
+        let input = "s red:a:1 blue:b:2\na blue:t:1\nb red:t:1";
+        let puzzle: Puzzle = input.parse().unwrap();
+
+        let paths = puzzle.solve_k("s".to_string(), "t".to_string(), 0, 1, 5, &HashMap::new());
+
+        let costs: Vec<usize> = paths.iter().map(|(_, cost)| *cost).collect();
+        assert_eq!(costs, vec![2, 3]);
+
+        let unique: HashSet<Vec<Edge>> = paths.into_iter().map(|(path, _)| path).collect();
+        assert_eq!(unique.len(), 2);
+    }
+
+    #[test]
+    fn test_edge_cost_prefers_explicit_then_color_weight_then_default() {
+        let color_weights = HashMap::from([("red".to_string(), 5)]);
+
+        let explicit = Edge {
+            color: Color::from("red"),
+            node: "b".to_string(),
+            cost: Some(2),
+        };
+        assert_eq!(explicit.cost(&color_weights), 2);
+
+        let weighted = Edge::new(Color::from("red"), "b".to_string());
+        assert_eq!(weighted.cost(&color_weights), 5);
+
+        let default = Edge::new(Color::from("blue"), "b".to_string());
+        assert_eq!(default.cost(&color_weights), 1);
+    }
+
+    #[test]
+    fn test_to_dot_labels_repeated_edge_with_every_order() {
+        // The solution traverses the "a" -red-> "b" edge twice (steps 1 and 3), so its DOT label
+        // should list both order numbers rather than only the first or last.
+        let input = "a red:b\nb blue:a";
+        let puzzle: Puzzle = input.parse().unwrap();
+        let solution = vec![
+            Edge {
+                color: Color::start(),
+                node: "a".to_string(),
+                cost: None,
+            },
+            Edge::new(Color::from("red"), "b".to_string()),
+            Edge::new(Color::from("blue"), "a".to_string()),
+            Edge::new(Color::from("red"), "b".to_string()),
+        ];
+
+        let dot = puzzle.to_dot(Some(&solution));
+
+        assert!(dot.contains("\"a\" -> \"b\" [label=\"red (1,3)\", color=\"red\", penwidth=3];"));
+        assert!(dot.contains("\"b\" -> \"a\" [label=\"blue (2)\", color=\"blue\", penwidth=3];"));
+    }
 }